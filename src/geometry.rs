@@ -0,0 +1,381 @@
+extern crate geo_types;
+
+use self::geo_types::{LineString, MultiPolygon, Polygon};
+use std::error;
+use std::fmt;
+
+use types::{Node, Relation, RelationMemberType, Way};
+
+/// Looks up a `Node` by id, so geometry conversion can work against however
+/// a caller happens to store their dataset (a `HashMap`, a database, a
+/// memory-mapped index, ...) rather than requiring an owned copy of it.
+pub trait NodeStore {
+    fn node(&self, id: i64) -> Option<&Node>;
+}
+
+/// A [`NodeStore`] that can additionally resolve the `Way`s referenced by a
+/// relation's members, needed to assemble multipolygon geometry.
+pub trait Resolver: NodeStore {
+    fn way(&self, id: i64) -> Option<&Way>;
+}
+
+/// Everything that can go wrong converting OSM elements to `geo_types`
+/// geometry: a referenced node/way that the resolver doesn't have, or a
+/// multipolygon whose member ways don't stitch into closed rings.
+#[derive(Debug)]
+pub enum GeometryError {
+    MissingNode(i64),
+    MissingWay(i64),
+    UnclosedRing,
+    NoContainingOuterRing,
+}
+
+impl fmt::Display for GeometryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            GeometryError::MissingNode(id) => write!(f, "node {} not found in resolver", id),
+            GeometryError::MissingWay(id) => write!(f, "way {} not found in resolver", id),
+            GeometryError::UnclosedRing => {
+                write!(f, "member ways do not stitch into a closed ring")
+            }
+            GeometryError::NoContainingOuterRing => {
+                write!(f, "inner ring is not contained by any outer ring")
+            }
+        }
+    }
+}
+
+impl error::Error for GeometryError {}
+
+/// Whether a way's first and last node references are the same id, i.e. it
+/// forms a closed ring.
+pub fn is_closed_way(way: &Way) -> bool {
+    way.nodes.len() > 1 && way.nodes.first() == way.nodes.last()
+}
+
+/// Whether a closed way should be treated as an area rather than a line,
+/// per the OSM convention: explicitly tagged `area=yes`, or closed and not
+/// tagged `highway=*`/`barrier=*` (those are almost always closed loops
+/// that are still lines, e.g. a roundabout or an enclosure wall).
+pub fn is_area(way: &Way) -> bool {
+    is_closed_way(way)
+        && (way.element_info.tags.get("area") == Some("yes")
+            || (!way.element_info.tags.contains_key("highway")
+                && !way.element_info.tags.contains_key("barrier")))
+}
+
+/// Resolve a way's `nodes` into a `LineString`.
+pub fn way_line_string<R: NodeStore>(
+    way: &Way,
+    nodes: &R,
+) -> Result<LineString<f64>, GeometryError> {
+    ring_from_ids(&way.nodes, nodes)
+}
+
+/// Resolve a way's `nodes` into a `Polygon`, if it forms a closed area per
+/// [`is_area`]. Returns `None` for ways that are lines rather than areas.
+pub fn way_polygon<R: NodeStore>(
+    way: &Way,
+    nodes: &R,
+) -> Result<Option<Polygon<f64>>, GeometryError> {
+    if !is_area(way) {
+        return Ok(None);
+    }
+    let ring = way_line_string(way, nodes)?;
+    Ok(Some(Polygon::new(ring, vec![])))
+}
+
+/// Assemble a `type=multipolygon`/`type=boundary` relation's `outer` and
+/// `inner` member ways into a `MultiPolygon`.
+///
+/// Member ways may be listed out of order or reversed, so ways sharing an
+/// endpoint are stitched together (flipping as needed) until each ring
+/// closes. Each inner ring becomes a hole of whichever outer ring contains
+/// it.
+pub fn relation_multi_polygon<R: Resolver>(
+    relation: &Relation,
+    resolver: &R,
+) -> Result<MultiPolygon<f64>, GeometryError> {
+    let mut outer_ways = Vec::new();
+    let mut inner_ways = Vec::new();
+
+    for member in &relation.members {
+        if member.member_type != RelationMemberType::Way {
+            continue;
+        }
+        let way = resolver
+            .way(member.reference)
+            .ok_or(GeometryError::MissingWay(member.reference))?;
+        match member.role.as_str() {
+            "outer" => outer_ways.push(way),
+            "inner" => inner_ways.push(way),
+            _ => {}
+        }
+    }
+
+    let outer_rings = assemble_rings(&outer_ways, resolver)?;
+    let inner_rings = assemble_rings(&inner_ways, resolver)?;
+
+    let mut holes: Vec<Vec<LineString<f64>>> = outer_rings.iter().map(|_| Vec::new()).collect();
+
+    'inner: for inner in inner_rings {
+        let (x, y) = first_point(&inner);
+        for (i, outer) in outer_rings.iter().enumerate() {
+            if ring_contains_point(outer, x, y) {
+                holes[i].push(inner);
+                continue 'inner;
+            }
+        }
+        return Err(GeometryError::NoContainingOuterRing);
+    }
+
+    let polygons = outer_rings
+        .into_iter()
+        .zip(holes)
+        .map(|(outer, holes)| Polygon::new(outer, holes))
+        .collect();
+
+    Ok(MultiPolygon(polygons))
+}
+
+/// Stitch a set of ways (identified only by their node-id chains) end to
+/// end into closed rings, flipping a way's direction when it connects at
+/// its end rather than its start.
+fn assemble_rings<R: NodeStore>(
+    ways: &[&Way],
+    nodes: &R,
+) -> Result<Vec<LineString<f64>>, GeometryError> {
+    let mut segments: Vec<Vec<i64>> = ways.iter().map(|way| way.nodes.clone()).collect();
+    let mut ring_ids = Vec::new();
+
+    while let Some(mut current) = segments.pop() {
+        while !(current.len() > 1 && current.first() == current.last()) {
+            let tail = *current.last().ok_or(GeometryError::UnclosedRing)?;
+            let position = segments
+                .iter()
+                .position(|segment| segment.first() == Some(&tail) || segment.last() == Some(&tail));
+
+            let mut next = match position {
+                Some(index) => segments.remove(index),
+                None => return Err(GeometryError::UnclosedRing),
+            };
+            if next.first() != Some(&tail) {
+                next.reverse();
+            }
+            next.remove(0);
+            current.extend(next);
+        }
+        ring_ids.push(current);
+    }
+
+    ring_ids
+        .into_iter()
+        .map(|ids| ring_from_ids(&ids, nodes))
+        .collect()
+}
+
+fn ring_from_ids<R: NodeStore>(ids: &[i64], nodes: &R) -> Result<LineString<f64>, GeometryError> {
+    let mut points = Vec::with_capacity(ids.len());
+    for &id in ids {
+        let node = nodes.node(id).ok_or(GeometryError::MissingNode(id))?;
+        points.push((Node::coord_itof(node.lon), Node::coord_itof(node.lat)));
+    }
+    Ok(LineString::from(points))
+}
+
+fn first_point(ring: &LineString<f64>) -> (f64, f64) {
+    let coord = ring.0[0];
+    (coord.x, coord.y)
+}
+
+/// Even-odd (ray casting) point-in-polygon test against a single ring.
+fn ring_contains_point(ring: &LineString<f64>, x: f64, y: f64) -> bool {
+    let points = &ring.0;
+    let n = points.len();
+    if n < 3 {
+        return false;
+    }
+
+    let mut inside = false;
+    let mut j = n - 1;
+    for i in 0..n {
+        let (xi, yi) = (points[i].x, points[i].y);
+        let (xj, yj) = (points[j].x, points[j].y);
+        if (yi > y) != (yj > y) && x < (xj - xi) * (y - yi) / (yj - yi) + xi {
+            inside = !inside;
+        }
+        j = i;
+    }
+    inside
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::HashMap;
+    use types::{ElementInfo, RelationMember};
+
+    struct TestStore {
+        nodes: HashMap<i64, Node>,
+        ways: HashMap<i64, Way>,
+    }
+
+    impl NodeStore for TestStore {
+        fn node(&self, id: i64) -> Option<&Node> {
+            self.nodes.get(&id)
+        }
+    }
+
+    impl Resolver for TestStore {
+        fn way(&self, id: i64) -> Option<&Way> {
+            self.ways.get(&id)
+        }
+    }
+
+    fn element_info(id: i64) -> ElementInfo {
+        ElementInfo {
+            id,
+            user: None,
+            uid: None,
+            timestamp: None,
+            visible: None,
+            version: None,
+            changeset: None,
+            tags: Default::default(),
+        }
+    }
+
+    fn node(id: i64, lon: f64, lat: f64) -> Node {
+        Node {
+            element_info: element_info(id),
+            lat: Node::coord_ftoi(lat),
+            lon: Node::coord_ftoi(lon),
+        }
+    }
+
+    fn way(id: i64, tags: &[(&str, &str)], nodes: Vec<i64>) -> Way {
+        let mut element_info = element_info(id);
+        for (k, v) in tags {
+            element_info.tags.insert(*k, *v);
+        }
+        Way { element_info, nodes }
+    }
+
+    fn square_store() -> TestStore {
+        let nodes = vec![
+            node(1, 0.0, 0.0),
+            node(2, 4.0, 0.0),
+            node(3, 4.0, 4.0),
+            node(4, 0.0, 4.0),
+        ]
+        .into_iter()
+        .map(|n| (n.element_info.id, n))
+        .collect();
+
+        TestStore {
+            nodes,
+            ways: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_way_line_string_resolves_points() {
+        let store = square_store();
+        let way = way(1, &[("highway", "residential")], vec![1, 2, 3]);
+
+        let line = way_line_string(&way, &store).unwrap();
+        assert_eq!(line.0.len(), 3);
+        assert_eq!((line.0[0].x, line.0[0].y), (0.0, 0.0));
+    }
+
+    #[test]
+    fn test_missing_node_is_an_error() {
+        let store = square_store();
+        let way = way(1, &[], vec![1, 99]);
+
+        match way_line_string(&way, &store) {
+            Err(GeometryError::MissingNode(99)) => {}
+            other => panic!("expected MissingNode(99), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_closed_way_without_highway_is_an_area() {
+        let store = square_store();
+        let building = way(1, &[("building", "yes")], vec![1, 2, 3, 4, 1]);
+        let road = way(2, &[("highway", "residential")], vec![1, 2, 3, 4, 1]);
+
+        assert!(way_polygon(&building, &store).unwrap().is_some());
+        assert!(way_polygon(&road, &store).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_multipolygon_attaches_hole_to_containing_outer_ring() {
+        let mut store = square_store();
+        store.nodes.insert(10, node(10, 1.0, 1.0));
+        store.nodes.insert(11, node(11, 2.0, 1.0));
+        store.nodes.insert(12, node(12, 2.0, 2.0));
+        store.nodes.insert(13, node(13, 1.0, 2.0));
+
+        let outer = way(100, &[], vec![1, 2, 3, 4, 1]);
+        let inner = way(101, &[], vec![10, 11, 12, 13, 10]);
+        store.ways.insert(100, outer);
+        store.ways.insert(101, inner);
+
+        let relation = Relation {
+            element_info: {
+                let mut info = element_info(1);
+                info.tags.insert("type", "multipolygon");
+                info
+            },
+            members: vec![
+                RelationMember {
+                    member_type: RelationMemberType::Way,
+                    reference: 100,
+                    role: "outer".to_string(),
+                },
+                RelationMember {
+                    member_type: RelationMemberType::Way,
+                    reference: 101,
+                    role: "inner".to_string(),
+                },
+            ],
+        };
+
+        let multi = relation_multi_polygon(&relation, &store).unwrap();
+        assert_eq!(multi.0.len(), 1);
+        assert_eq!(multi.0[0].interiors().len(), 1);
+    }
+
+    #[test]
+    fn test_multipolygon_stitches_reversed_out_of_order_members() {
+        let store = square_store();
+        let mut store = TestStore {
+            nodes: store.nodes,
+            ways: HashMap::new(),
+        };
+        // Two half-rings, the second one reversed, listed out of order.
+        store.ways.insert(1, way(1, &[], vec![3, 4, 1]));
+        store.ways.insert(2, way(2, &[], vec![3, 2, 1]));
+
+        let relation = Relation {
+            element_info: element_info(1),
+            members: vec![
+                RelationMember {
+                    member_type: RelationMemberType::Way,
+                    reference: 2,
+                    role: "outer".to_string(),
+                },
+                RelationMember {
+                    member_type: RelationMemberType::Way,
+                    reference: 1,
+                    role: "outer".to_string(),
+                },
+            ],
+        };
+
+        let multi = relation_multi_polygon(&relation, &store).unwrap();
+        assert_eq!(multi.0.len(), 1);
+        assert_eq!(multi.0[0].exterior().0.len(), 5);
+    }
+}
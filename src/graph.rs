@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use types::Way;
+
+/// A broad classification of a way's `highway=*` value, coarse enough to
+/// drive routing heuristics (e.g. preferring `Primary` over `Residential`)
+/// without callers having to match on the raw tag value themselves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RoadClass {
+    Motorway,
+    Trunk,
+    Primary,
+    Secondary,
+    Tertiary,
+    Residential,
+    Service,
+    Track,
+    Path,
+    Other,
+}
+
+impl RoadClass {
+    /// Classify a `highway=*` tag value, folding `_link` variants into their
+    /// parent class.
+    pub fn classify(highway: &str) -> RoadClass {
+        match highway {
+            "motorway" | "motorway_link" => RoadClass::Motorway,
+            "trunk" | "trunk_link" => RoadClass::Trunk,
+            "primary" | "primary_link" => RoadClass::Primary,
+            "secondary" | "secondary_link" => RoadClass::Secondary,
+            "tertiary" | "tertiary_link" => RoadClass::Tertiary,
+            "residential" | "living_street" | "unclassified" => RoadClass::Residential,
+            "service" => RoadClass::Service,
+            "track" => RoadClass::Track,
+            "path" | "footway" | "cycleway" | "pedestrian" | "steps" => RoadClass::Path,
+            _ => RoadClass::Other,
+        }
+    }
+}
+
+/// Build a directed edge list from a set of `Way`s, mirroring how
+/// OpenStreetMapX derives a routable graph from raw OSM data.
+///
+/// Only ways tagged `highway=*` contribute edges. For each consecutive pair
+/// of node references in a way, `oneway` is honoured: `yes`/`true`/`1` makes
+/// the edge forward-only, `-1`/`reverse` marks the way as a reverse way (its
+/// node order is logically flipped), and anything else produces an edge in
+/// both directions. Edges are keyed on the ordered node pair, so two ways
+/// that share a segment naturally collapse to one entry.
+///
+/// Node coordinates aren't needed here; resolve them afterwards through a
+/// node index keyed on the ids appearing in the returned edges.
+pub fn build_edges(ways: &[Way]) -> HashMap<(i64, i64), RoadClass> {
+    let mut edges = HashMap::new();
+
+    for way in ways {
+        let highway = match way.element_info.tags.get("highway") {
+            Some(highway) => highway,
+            None => continue,
+        };
+        let class = RoadClass::classify(highway);
+
+        let oneway_tag = way.element_info.tags.get("oneway");
+        let reverse = matches!(oneway_tag, Some("-1") | Some("reverse"));
+        let oneway = reverse || matches!(oneway_tag, Some("yes") | Some("true") | Some("1"));
+
+        for pair in way.nodes.windows(2) {
+            let (n0, n1) = (pair[0], pair[1]);
+            let (start, fin) = if reverse { (n1, n0) } else { (n0, n1) };
+
+            edges.insert((start, fin), class);
+            if !oneway {
+                edges.insert((fin, start), class);
+            }
+        }
+    }
+
+    edges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use types::ElementInfo;
+
+    fn way(id: i64, tags: &[(&str, &str)], nodes: Vec<i64>) -> Way {
+        let mut element_info = ElementInfo {
+            id,
+            user: None,
+            uid: None,
+            timestamp: None,
+            visible: None,
+            version: None,
+            changeset: None,
+            tags: Default::default(),
+        };
+        for (k, v) in tags {
+            element_info.tags.insert(*k, *v);
+        }
+        Way {
+            element_info,
+            nodes,
+        }
+    }
+
+    #[test]
+    fn test_two_way_street_gets_both_directions() {
+        let ways = vec![way(1, &[("highway", "residential")], vec![10, 20, 30])];
+        let edges = build_edges(&ways);
+
+        assert_eq!(edges.get(&(10, 20)), Some(&RoadClass::Residential));
+        assert_eq!(edges.get(&(20, 10)), Some(&RoadClass::Residential));
+        assert_eq!(edges.get(&(20, 30)), Some(&RoadClass::Residential));
+        assert_eq!(edges.get(&(30, 20)), Some(&RoadClass::Residential));
+    }
+
+    #[test]
+    fn test_oneway_yes_is_forward_only() {
+        let ways = vec![way(
+            1,
+            &[("highway", "primary"), ("oneway", "yes")],
+            vec![1, 2],
+        )];
+        let edges = build_edges(&ways);
+
+        assert_eq!(edges.get(&(1, 2)), Some(&RoadClass::Primary));
+        assert_eq!(edges.get(&(2, 1)), None);
+    }
+
+    #[test]
+    fn test_reverse_way_swaps_start_and_fin() {
+        let ways = vec![way(
+            1,
+            &[("highway", "primary"), ("oneway", "-1")],
+            vec![1, 2],
+        )];
+        let edges = build_edges(&ways);
+
+        assert_eq!(edges.get(&(2, 1)), Some(&RoadClass::Primary));
+        assert_eq!(edges.get(&(1, 2)), None);
+    }
+
+    #[test]
+    fn test_non_highway_way_contributes_no_edges() {
+        let ways = vec![way(1, &[("building", "yes")], vec![1, 2])];
+        assert!(build_edges(&ways).is_empty());
+    }
+}
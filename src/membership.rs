@@ -0,0 +1,141 @@
+use std::collections::HashMap;
+
+use types::{Relation, RelationMemberType};
+
+/// A `Relation`'s `ElementInfo::id`.
+pub type RelationId = i64;
+
+/// An index from node/way ids to the relations that reference them (and the
+/// role they play), built once from a `Relation` collection.
+///
+/// Following tilemaker's relation model, this lets callers ask "which
+/// relations is this way part of?" in O(1) while iterating ways — e.g. to
+/// colour a road segment that belongs to a `route=bus`/`route=bicycle`
+/// relation, or to read a reference number off a parent route relation —
+/// instead of rescanning every relation's members per way.
+pub struct Membership<'a> {
+    ways: HashMap<i64, Vec<(RelationId, &'a str)>>,
+    nodes: HashMap<i64, Vec<(RelationId, &'a str)>>,
+}
+
+impl<'a> Membership<'a> {
+    /// Scan `relations` once, indexing each member by its referenced id.
+    pub fn build(relations: &'a [Relation]) -> Membership<'a> {
+        let mut ways: HashMap<i64, Vec<(RelationId, &'a str)>> = HashMap::new();
+        let mut nodes: HashMap<i64, Vec<(RelationId, &'a str)>> = HashMap::new();
+
+        for relation in relations {
+            for member in &relation.members {
+                let index = match member.member_type {
+                    RelationMemberType::Way => &mut ways,
+                    RelationMemberType::Node => &mut nodes,
+                    RelationMemberType::Relation => continue,
+                };
+                index
+                    .entry(member.reference)
+                    .or_default()
+                    .push((relation.element_info.id, member.role.as_str()));
+            }
+        }
+
+        Membership { ways, nodes }
+    }
+
+    /// The relations `id` (a way id) is a member of, with its role in each.
+    pub fn relations_of_way(&self, id: i64) -> &[(RelationId, &'a str)] {
+        self.ways.get(&id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// The relations `id` (a node id) is a member of, with its role in each.
+    pub fn relations_of_node(&self, id: i64) -> &[(RelationId, &'a str)] {
+        self.nodes.get(&id).map(Vec::as_slice).unwrap_or(&[])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use types::{ElementInfo, RelationMember};
+
+    fn element_info(id: i64) -> ElementInfo {
+        ElementInfo {
+            id,
+            user: None,
+            uid: None,
+            timestamp: None,
+            visible: None,
+            version: None,
+            changeset: None,
+            tags: Default::default(),
+        }
+    }
+
+    fn relation(id: i64, members: Vec<RelationMember>) -> Relation {
+        Relation {
+            element_info: element_info(id),
+            members,
+        }
+    }
+
+    #[test]
+    fn test_relations_of_way_reports_role() {
+        let relations = vec![relation(
+            1,
+            vec![RelationMember {
+                member_type: RelationMemberType::Way,
+                reference: 10,
+                role: "outer".to_string(),
+            }],
+        )];
+
+        let membership = Membership::build(&relations);
+
+        assert_eq!(membership.relations_of_way(10), &[(1, "outer")]);
+        assert_eq!(membership.relations_of_way(99), &[]);
+    }
+
+    #[test]
+    fn test_way_in_multiple_relations_is_indexed_under_each() {
+        let relations = vec![
+            relation(
+                1,
+                vec![RelationMember {
+                    member_type: RelationMemberType::Way,
+                    reference: 10,
+                    role: "".to_string(),
+                }],
+            ),
+            relation(
+                2,
+                vec![RelationMember {
+                    member_type: RelationMemberType::Way,
+                    reference: 10,
+                    role: "".to_string(),
+                }],
+            ),
+        ];
+
+        let membership = Membership::build(&relations);
+        let mut ids: Vec<_> = membership.relations_of_way(10).iter().map(|(id, _)| *id).collect();
+        ids.sort();
+
+        assert_eq!(ids, vec![1, 2]);
+    }
+
+    #[test]
+    fn test_relations_of_node_is_separate_from_ways() {
+        let relations = vec![relation(
+            1,
+            vec![RelationMember {
+                member_type: RelationMemberType::Node,
+                reference: 5,
+                role: "stop".to_string(),
+            }],
+        )];
+
+        let membership = Membership::build(&relations);
+
+        assert_eq!(membership.relations_of_node(5), &[(1, "stop")]);
+        assert_eq!(membership.relations_of_way(5), &[]);
+    }
+}
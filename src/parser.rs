@@ -0,0 +1,430 @@
+extern crate chrono;
+extern crate xml;
+
+use self::chrono::{DateTime, Utc};
+use self::xml::attribute::OwnedAttribute;
+use self::xml::reader::{EventReader, XmlEvent};
+use std::error;
+use std::fmt;
+use std::io::Read;
+
+use tags::Tags;
+use types::{ElementInfo, Node, Relation, RelationMember, RelationMemberType, Way};
+
+/// Everything that can go wrong while reading an `.osm` XML document: a
+/// malformed document, or an element/attribute that doesn't match the
+/// format this parser understands.
+#[derive(Debug)]
+pub enum ParseError {
+    Xml(self::xml::reader::Error),
+    MissingAttribute {
+        element: &'static str,
+        attribute: &'static str,
+    },
+    InvalidAttribute {
+        element: &'static str,
+        attribute: &'static str,
+        value: String,
+    },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ParseError::Xml(ref e) => write!(f, "malformed OSM XML: {}", e),
+            ParseError::MissingAttribute { element, attribute } => write!(
+                f,
+                "<{}> is missing required attribute `{}`",
+                element, attribute
+            ),
+            ParseError::InvalidAttribute {
+                element,
+                attribute,
+                ref value,
+            } => write!(
+                f,
+                "<{}> has invalid `{}` attribute: {:?}",
+                element, attribute, value
+            ),
+        }
+    }
+}
+
+impl error::Error for ParseError {}
+
+impl From<self::xml::reader::Error> for ParseError {
+    fn from(error: self::xml::reader::Error) -> ParseError {
+        ParseError::Xml(error)
+    }
+}
+
+/// A single top-level element yielded while streaming an `.osm` document.
+#[derive(Debug)]
+pub enum Element {
+    Node(Node),
+    Way(Way),
+    Relation(Relation),
+}
+
+/// The result of fully materializing an `.osm` XML document in memory.
+///
+/// See [`OsmReader`] for a streaming alternative that doesn't require the whole
+/// document to be loaded at once.
+#[derive(Debug, Default)]
+pub struct Osm {
+    pub nodes: Vec<Node>,
+    pub ways: Vec<Way>,
+    pub relations: Vec<Relation>,
+}
+
+/// Parse a complete `.osm` XML document into an [`Osm`], holding every
+/// element in memory at once.
+pub fn parse<R: Read>(source: R) -> Result<Osm, ParseError> {
+    let mut osm = Osm::default();
+    for element in OsmReader::new(source) {
+        match element? {
+            Element::Node(node) => osm.nodes.push(node),
+            Element::Way(way) => osm.ways.push(way),
+            Element::Relation(relation) => osm.relations.push(relation),
+        }
+    }
+    Ok(osm)
+}
+
+/// Streams the `<node>`, `<way>` and `<relation>` elements of an `.osm` XML
+/// document one at a time, so large extracts don't need to be held fully in
+/// memory. Yields each top-level element as it is closed.
+pub struct OsmReader<R: Read> {
+    events: EventReader<R>,
+}
+
+impl<R: Read> OsmReader<R> {
+    pub fn new(source: R) -> OsmReader<R> {
+        OsmReader {
+            events: EventReader::new(source),
+        }
+    }
+}
+
+impl<R: Read> Iterator for OsmReader<R> {
+    type Item = Result<Element, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.events.next() {
+                Ok(XmlEvent::StartElement {
+                    name, attributes, ..
+                }) => {
+                    let element = match name.local_name.as_str() {
+                        "node" => self.read_node(&attributes).map(Element::Node),
+                        "way" => self.read_way(&attributes).map(Element::Way),
+                        "relation" => self.read_relation(&attributes).map(Element::Relation),
+                        _ => continue,
+                    };
+                    return Some(element);
+                }
+                Ok(XmlEvent::EndDocument) => return None,
+                Ok(_) => continue,
+                Err(e) => return Some(Err(ParseError::from(e))),
+            }
+        }
+    }
+}
+
+impl<R: Read> OsmReader<R> {
+    fn read_node(&mut self, attributes: &[OwnedAttribute]) -> Result<Node, ParseError> {
+        let mut element_info = element_info("node", attributes)?;
+        let lat = required_attr("node", attributes, "lat")?
+            .parse::<f64>()
+            .map_err(|_| invalid_attr("node", "lat", attributes))?;
+        let lon = required_attr("node", attributes, "lon")?
+            .parse::<f64>()
+            .map_err(|_| invalid_attr("node", "lon", attributes))?;
+
+        consume_children(&mut self.events, "node", |name, attrs| {
+            if name == "tag" {
+                if let (Some(k), Some(v)) = (attr(attrs, "k"), attr(attrs, "v")) {
+                    element_info.tags.insert(k, v);
+                }
+            }
+            Ok(())
+        })?;
+
+        Ok(Node {
+            element_info,
+            lat: Node::coord_ftoi(lat),
+            lon: Node::coord_ftoi(lon),
+        })
+    }
+
+    fn read_way(&mut self, attributes: &[OwnedAttribute]) -> Result<Way, ParseError> {
+        let mut element_info = element_info("way", attributes)?;
+        let mut nodes = Vec::new();
+
+        consume_children(&mut self.events, "way", |name, attrs| {
+            match name {
+                "tag" => {
+                    if let (Some(k), Some(v)) = (attr(attrs, "k"), attr(attrs, "v")) {
+                        element_info.tags.insert(k, v);
+                    }
+                }
+                "nd" => {
+                    if let Some(reference) = attr(attrs, "ref") {
+                        let reference = reference
+                            .parse::<i64>()
+                            .map_err(|_| invalid_attr("nd", "ref", attrs))?;
+                        nodes.push(reference);
+                    }
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+
+        Ok(Way {
+            element_info,
+            nodes,
+        })
+    }
+
+    fn read_relation(&mut self, attributes: &[OwnedAttribute]) -> Result<Relation, ParseError> {
+        let mut element_info = element_info("relation", attributes)?;
+        let mut members = Vec::new();
+
+        consume_children(&mut self.events, "relation", |name, attrs| {
+            match name {
+                "tag" => {
+                    if let (Some(k), Some(v)) = (attr(attrs, "k"), attr(attrs, "v")) {
+                        element_info.tags.insert(k, v);
+                    }
+                }
+                "member" => {
+                    let member_type = required_attr("member", attrs, "type")?;
+                    let member_type = match member_type {
+                        "node" => RelationMemberType::Node,
+                        "way" => RelationMemberType::Way,
+                        "relation" => RelationMemberType::Relation,
+                        _ => return Err(invalid_attr("member", "type", attrs)),
+                    };
+                    let reference = required_attr("member", attrs, "ref")?
+                        .parse::<i64>()
+                        .map_err(|_| invalid_attr("member", "ref", attrs))?;
+                    let role = attr(attrs, "role").unwrap_or("").to_string();
+
+                    members.push(RelationMember {
+                        member_type,
+                        reference,
+                        role,
+                    });
+                }
+                _ => {}
+            }
+            Ok(())
+        })?;
+
+        Ok(Relation {
+            element_info,
+            members,
+        })
+    }
+}
+
+/// Drive `events` past the children of the element that was just opened,
+/// invoking `on_child` for each immediate child's local name and attributes,
+/// until the matching end tag (`end_name`) is reached.
+fn consume_children<R, F>(
+    events: &mut EventReader<R>,
+    end_name: &str,
+    mut on_child: F,
+) -> Result<(), ParseError>
+where
+    R: Read,
+    F: FnMut(&str, &[OwnedAttribute]) -> Result<(), ParseError>,
+{
+    let mut depth = 0usize;
+    loop {
+        match events.next()? {
+            XmlEvent::StartElement {
+                name, attributes, ..
+            } => {
+                if depth == 0 {
+                    on_child(&name.local_name, &attributes)?;
+                }
+                depth += 1;
+            }
+            XmlEvent::EndElement { name } => {
+                if depth == 0 {
+                    debug_assert_eq!(name.local_name, end_name);
+                    return Ok(());
+                }
+                depth -= 1;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn element_info(element: &'static str, attributes: &[OwnedAttribute]) -> Result<ElementInfo, ParseError> {
+    let id = required_attr(element, attributes, "id")?
+        .parse::<i64>()
+        .map_err(|_| invalid_attr(element, "id", attributes))?;
+
+    let user = attr(attributes, "user").map(str::to_string);
+    let uid = match attr(attributes, "uid") {
+        Some(v) => Some(
+            v.parse::<i32>()
+                .map_err(|_| invalid_attr(element, "uid", attributes))?,
+        ),
+        None => None,
+    };
+    let timestamp = match attr(attributes, "timestamp") {
+        Some(v) => Some(
+            DateTime::parse_from_rfc3339(v)
+                .map(|dt| dt.with_timezone(&Utc))
+                .map_err(|_| invalid_attr(element, "timestamp", attributes))?,
+        ),
+        None => None,
+    };
+    let visible = match attr(attributes, "visible") {
+        Some(v) => Some(
+            v.parse::<bool>()
+                .map_err(|_| invalid_attr(element, "visible", attributes))?,
+        ),
+        None => None,
+    };
+    let version = match attr(attributes, "version") {
+        Some(v) => Some(
+            v.parse::<i32>()
+                .map_err(|_| invalid_attr(element, "version", attributes))?,
+        ),
+        None => None,
+    };
+    let changeset = match attr(attributes, "changeset") {
+        Some(v) => Some(
+            v.parse::<i64>()
+                .map_err(|_| invalid_attr(element, "changeset", attributes))?,
+        ),
+        None => None,
+    };
+
+    Ok(ElementInfo {
+        id,
+        user,
+        uid,
+        timestamp,
+        visible,
+        version,
+        changeset,
+        tags: Tags::new(),
+    })
+}
+
+fn attr<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|a| a.name.local_name == name)
+        .map(|a| a.value.as_str())
+}
+
+fn required_attr<'a>(
+    element: &'static str,
+    attributes: &'a [OwnedAttribute],
+    name: &'static str,
+) -> Result<&'a str, ParseError> {
+    attr(attributes, name).ok_or(ParseError::MissingAttribute {
+        element,
+        attribute: name,
+    })
+}
+
+fn invalid_attr(element: &'static str, attribute: &'static str, attributes: &[OwnedAttribute]) -> ParseError {
+    ParseError::InvalidAttribute {
+        element,
+        attribute,
+        value: attr(attributes, attribute).unwrap_or("").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SAMPLE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<osm version="0.6">
+  <node id="1" user="alice" uid="42" timestamp="2021-05-06T12:30:00Z" visible="true" version="2" changeset="100" lat="51.5098650" lon="-0.1180920">
+    <tag k="amenity" v="cafe"/>
+  </node>
+  <node id="2" lat="51.5100000" lon="-0.1190000"/>
+  <way id="10" version="1">
+    <nd ref="1"/>
+    <nd ref="2"/>
+    <tag k="highway" v="residential"/>
+  </way>
+  <relation id="100">
+    <member type="way" ref="10" role="outer"/>
+    <tag k="type" v="multipolygon"/>
+  </relation>
+</osm>"#;
+
+    #[test]
+    fn test_parse_materializes_all_elements() {
+        let osm = parse(SAMPLE.as_bytes()).unwrap();
+
+        assert_eq!(osm.nodes.len(), 2);
+        assert_eq!(osm.ways.len(), 1);
+        assert_eq!(osm.relations.len(), 1);
+
+        let cafe = &osm.nodes[0];
+        assert_eq!(cafe.element_info.id, 1);
+        assert_eq!(cafe.element_info.user.as_deref(), Some("alice"));
+        assert_eq!(cafe.element_info.tags.get("amenity"), Some("cafe"));
+        assert_eq!(Node::coord_itof(cafe.lat), 51.509865);
+
+        let way = &osm.ways[0];
+        assert_eq!(way.nodes, vec![1, 2]);
+        assert_eq!(way.element_info.tags.get("highway"), Some("residential"));
+
+        let relation = &osm.relations[0];
+        assert_eq!(relation.members.len(), 1);
+        assert_eq!(relation.members[0].member_type, RelationMemberType::Way);
+        assert_eq!(relation.members[0].reference, 10);
+        assert_eq!(relation.members[0].role, "outer");
+    }
+
+    #[test]
+    fn test_streaming_reader_yields_same_elements_as_parse() {
+        let elements: Vec<Element> = OsmReader::new(SAMPLE.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(elements.len(), 4);
+
+        let osm = parse(SAMPLE.as_bytes()).unwrap();
+        let streamed: Vec<i64> = elements
+            .iter()
+            .map(|element| match element {
+                Element::Node(node) => node.element_info.id,
+                Element::Way(way) => way.element_info.id,
+                Element::Relation(relation) => relation.element_info.id,
+            })
+            .collect();
+        let parsed: Vec<i64> = osm.nodes.iter().map(|n| n.element_info.id)
+            .chain(osm.ways.iter().map(|w| w.element_info.id))
+            .chain(osm.relations.iter().map(|r| r.element_info.id))
+            .collect();
+        assert_eq!(streamed, parsed);
+    }
+
+    #[test]
+    fn test_missing_required_attribute_is_an_error() {
+        let bad = r#"<osm><node lat="1.0" lon="2.0"/></osm>"#;
+        let err = parse(bad.as_bytes()).unwrap_err();
+        match err {
+            ParseError::MissingAttribute { element, attribute } => {
+                assert_eq!(element, "node");
+                assert_eq!(attribute, "id");
+            }
+            other => panic!("expected MissingAttribute, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,428 @@
+use std::collections::HashMap;
+use std::error;
+use std::fmt;
+
+use types::{Relation, RelationMemberType};
+
+/// The `via` member(s) of a turn restriction: either a single node where all
+/// the ways meet, or one or more connected ways when a node alone can't
+/// pin down the junction (e.g. a short slip road between two ways).
+#[derive(Debug, Clone, PartialEq)]
+pub enum ViaMember {
+    Node(i64),
+    Ways(Vec<i64>),
+}
+
+/// The kind of turn a `type=restriction` relation encodes, parsed from its
+/// `restriction` tag.
+///
+/// See: [OSM wiki - Relation:restriction](https://wiki.openstreetmap.org/wiki/Relation:restriction)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestrictionKind {
+    NoLeftTurn,
+    NoRightTurn,
+    NoStraightOn,
+    NoUTurn,
+    OnlyLeftTurn,
+    OnlyRightTurn,
+    OnlyStraightOn,
+    OnlyUTurn,
+}
+
+impl RestrictionKind {
+    fn parse(value: &str) -> Option<RestrictionKind> {
+        match value {
+            "no_left_turn" => Some(RestrictionKind::NoLeftTurn),
+            "no_right_turn" => Some(RestrictionKind::NoRightTurn),
+            "no_straight_on" => Some(RestrictionKind::NoStraightOn),
+            "no_u_turn" => Some(RestrictionKind::NoUTurn),
+            "only_left_turn" => Some(RestrictionKind::OnlyLeftTurn),
+            "only_right_turn" => Some(RestrictionKind::OnlyRightTurn),
+            "only_straight_on" => Some(RestrictionKind::OnlyStraightOn),
+            "only_u_turn" => Some(RestrictionKind::OnlyUTurn),
+            _ => None,
+        }
+    }
+
+    /// Whether this is a mandatory (`only_*`) restriction rather than a
+    /// prohibitive (`no_*`) one.
+    pub fn is_mandatory(&self) -> bool {
+        matches!(
+            *self,
+            RestrictionKind::OnlyLeftTurn
+                | RestrictionKind::OnlyRightTurn
+                | RestrictionKind::OnlyStraightOn
+                | RestrictionKind::OnlyUTurn
+        )
+    }
+}
+
+/// A parsed `type=restriction` relation: transitioning from `from_way` to
+/// `to_way` via `via` is forbidden, or (for `only_*` kinds) the only
+/// transition permitted out of `from_way` at `via`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TurnRestriction {
+    pub from_way: i64,
+    pub via: ViaMember,
+    pub to_way: i64,
+    pub kind: RestrictionKind,
+}
+
+/// Everything that can go wrong interpreting a `type=restriction` relation.
+#[derive(Debug)]
+pub enum RestrictionError {
+    MissingFromWay(i64),
+    MissingToWay(i64),
+    MissingVia(i64),
+    UnknownKind(i64, String),
+}
+
+impl fmt::Display for RestrictionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            RestrictionError::MissingFromWay(id) => {
+                write!(f, "restriction relation {} has no `from` way member", id)
+            }
+            RestrictionError::MissingToWay(id) => {
+                write!(f, "restriction relation {} has no `to` way member", id)
+            }
+            RestrictionError::MissingVia(id) => {
+                write!(f, "restriction relation {} has no `via` member", id)
+            }
+            RestrictionError::UnknownKind(id, ref value) => write!(
+                f,
+                "restriction relation {} has unrecognised restriction={:?}",
+                id, value
+            ),
+        }
+    }
+}
+
+impl error::Error for RestrictionError {}
+
+/// Parse `relation` into a `TurnRestriction` if it is tagged
+/// `type=restriction`. Returns `Ok(None)` for any other relation, so callers
+/// can map this over a full relation collection without pre-filtering.
+pub fn parse_restriction(
+    relation: &Relation,
+) -> Result<Option<TurnRestriction>, RestrictionError> {
+    if relation.element_info.tags.get("type") != Some("restriction") {
+        return Ok(None);
+    }
+
+    let id = relation.element_info.id;
+    let mut from_way = None;
+    let mut to_way = None;
+    let mut via_node = None;
+    let mut via_ways = Vec::new();
+
+    for member in &relation.members {
+        match (member.member_type, member.role.as_str()) {
+            (RelationMemberType::Way, "from") => from_way = Some(member.reference),
+            (RelationMemberType::Way, "to") => to_way = Some(member.reference),
+            (RelationMemberType::Node, "via") => via_node = Some(member.reference),
+            (RelationMemberType::Way, "via") => via_ways.push(member.reference),
+            _ => {}
+        }
+    }
+
+    let from_way = from_way.ok_or(RestrictionError::MissingFromWay(id))?;
+    let to_way = to_way.ok_or(RestrictionError::MissingToWay(id))?;
+    let via = match via_node {
+        Some(node) => ViaMember::Node(node),
+        None if !via_ways.is_empty() => ViaMember::Ways(via_ways),
+        None => return Err(RestrictionError::MissingVia(id)),
+    };
+
+    let restriction_tag = relation.element_info.tags.get("restriction");
+    let kind = restriction_tag.and_then(RestrictionKind::parse).ok_or_else(|| {
+        RestrictionError::UnknownKind(id, restriction_tag.unwrap_or("").to_string())
+    })?;
+
+    Ok(Some(TurnRestriction {
+        from_way,
+        via,
+        to_way,
+        kind,
+    }))
+}
+
+/// Indexes parsed turn restrictions by their `via` node or way(s), so a
+/// router can ask at a given junction whether continuing onto a particular
+/// outgoing way is allowed.
+pub struct RestrictionIndex {
+    restrictions: Vec<TurnRestriction>,
+    by_via_node: HashMap<i64, Vec<usize>>,
+    by_via_way: HashMap<i64, Vec<usize>>,
+    skipped: Vec<RestrictionError>,
+}
+
+impl RestrictionIndex {
+    /// Parse every `type=restriction` relation in `relations` and index it
+    /// by its `via` member(s).
+    ///
+    /// Real OSM extracts routinely contain malformed `type=restriction`
+    /// relations (a missing `from`/`to`/`via` member, or an unrecognised
+    /// `restriction=` value), so a relation that fails to parse is skipped
+    /// rather than aborting the whole index — one bad relation shouldn't
+    /// discard every other restriction that parsed fine. Skipped relations'
+    /// errors are kept and available via [`RestrictionIndex::skipped`].
+    pub fn build(relations: &[Relation]) -> RestrictionIndex {
+        let mut restrictions = Vec::new();
+        let mut skipped = Vec::new();
+        for relation in relations {
+            match parse_restriction(relation) {
+                Ok(Some(restriction)) => restrictions.push(restriction),
+                Ok(None) => {}
+                Err(error) => skipped.push(error),
+            }
+        }
+
+        let mut by_via_node: HashMap<i64, Vec<usize>> = HashMap::new();
+        let mut by_via_way: HashMap<i64, Vec<usize>> = HashMap::new();
+
+        for (index, restriction) in restrictions.iter().enumerate() {
+            match &restriction.via {
+                ViaMember::Node(node_id) => by_via_node.entry(*node_id).or_default().push(index),
+                ViaMember::Ways(way_ids) => {
+                    for way_id in way_ids {
+                        by_via_way.entry(*way_id).or_default().push(index);
+                    }
+                }
+            }
+        }
+
+        RestrictionIndex {
+            restrictions,
+            by_via_node,
+            by_via_way,
+            skipped,
+        }
+    }
+
+    /// `type=restriction` relations that were skipped because they failed
+    /// to parse, in the order they were encountered.
+    pub fn skipped(&self) -> &[RestrictionError] {
+        &self.skipped
+    }
+
+    /// Restrictions whose `via` is the node `id`.
+    pub fn via_node(&self, id: i64) -> impl Iterator<Item = &TurnRestriction> {
+        self.by_via_node
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(move |&i| &self.restrictions[i])
+    }
+
+    /// Restrictions whose `via` includes the way `id`.
+    pub fn via_way(&self, id: i64) -> impl Iterator<Item = &TurnRestriction> {
+        self.by_via_way
+            .get(&id)
+            .into_iter()
+            .flatten()
+            .map(move |&i| &self.restrictions[i])
+    }
+
+    /// Whether a router may continue from `from_way` to `to_way` through the
+    /// junction `via`: forbidden by a matching `no_*` restriction, or — when
+    /// an `only_*` restriction applies to `from_way` at `via` — only allowed
+    /// if `to_way` is the one it mandates.
+    ///
+    /// `via` is looked up as both a via-node id and a via-way id (a
+    /// restriction whose `via` is a chain of ways, parsed into
+    /// `ViaMember::Ways`, is indexed under each of those way ids), so pass
+    /// whichever one the router is currently transitioning through —
+    /// callers don't need to special-case way-chain restrictions themselves.
+    pub fn is_turn_allowed(&self, from_way: i64, via: i64, to_way: i64) -> bool {
+        let mut mandated: Option<i64> = None;
+
+        for restriction in self.via_node(via).chain(self.via_way(via)) {
+            if restriction.from_way != from_way {
+                continue;
+            }
+            if restriction.kind.is_mandatory() {
+                mandated = Some(restriction.to_way);
+            } else if restriction.to_way == to_way {
+                return false;
+            }
+        }
+
+        match mandated {
+            Some(only) => only == to_way,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use types::{ElementInfo, RelationMember};
+
+    fn element_info(id: i64, tags: &[(&str, &str)]) -> ElementInfo {
+        let mut info = ElementInfo {
+            id,
+            user: None,
+            uid: None,
+            timestamp: None,
+            visible: None,
+            version: None,
+            changeset: None,
+            tags: Default::default(),
+        };
+        for (k, v) in tags {
+            info.tags.insert(*k, *v);
+        }
+        info
+    }
+
+    fn member(member_type: RelationMemberType, reference: i64, role: &str) -> RelationMember {
+        RelationMember {
+            member_type,
+            reference,
+            role: role.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_no_left_turn_via_node() {
+        let relation = Relation {
+            element_info: element_info(1, &[("type", "restriction"), ("restriction", "no_left_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Node, 5, "via"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        };
+
+        let restriction = parse_restriction(&relation).unwrap().unwrap();
+        assert_eq!(restriction.from_way, 10);
+        assert_eq!(restriction.via, ViaMember::Node(5));
+        assert_eq!(restriction.to_way, 20);
+        assert_eq!(restriction.kind, RestrictionKind::NoLeftTurn);
+    }
+
+    #[test]
+    fn test_parse_via_way_chain() {
+        let relation = Relation {
+            element_info: element_info(1, &[("type", "restriction"), ("restriction", "no_u_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Way, 15, "via"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        };
+
+        let restriction = parse_restriction(&relation).unwrap().unwrap();
+        assert_eq!(restriction.via, ViaMember::Ways(vec![15]));
+    }
+
+    #[test]
+    fn test_non_restriction_relation_is_ignored() {
+        let relation = Relation {
+            element_info: element_info(1, &[("type", "multipolygon")]),
+            members: vec![],
+        };
+
+        assert_eq!(parse_restriction(&relation).unwrap(), None);
+    }
+
+    #[test]
+    fn test_missing_via_is_an_error() {
+        let relation = Relation {
+            element_info: element_info(1, &[("type", "restriction"), ("restriction", "no_u_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        };
+
+        match parse_restriction(&relation) {
+            Err(RestrictionError::MissingVia(1)) => {}
+            other => panic!("expected MissingVia(1), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_index_no_turn_is_forbidden_only_for_matching_from_way() {
+        let relations = vec![Relation {
+            element_info: element_info(1, &[("type", "restriction"), ("restriction", "no_left_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Node, 5, "via"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        }];
+
+        let index = RestrictionIndex::build(&relations);
+
+        assert!(!index.is_turn_allowed(10, 5, 20));
+        assert!(index.is_turn_allowed(10, 5, 30));
+        assert!(index.is_turn_allowed(99, 5, 20));
+    }
+
+    #[test]
+    fn test_index_only_turn_forbids_every_other_destination() {
+        let relations = vec![Relation {
+            element_info: element_info(
+                1,
+                &[("type", "restriction"), ("restriction", "only_straight_on")],
+            ),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Node, 5, "via"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        }];
+
+        let index = RestrictionIndex::build(&relations);
+
+        assert!(index.is_turn_allowed(10, 5, 20));
+        assert!(!index.is_turn_allowed(10, 5, 30));
+    }
+
+    #[test]
+    fn test_index_skips_malformed_relations_without_discarding_the_rest() {
+        let malformed = Relation {
+            element_info: element_info(1, &[("type", "restriction"), ("restriction", "no_u_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        };
+        let valid = Relation {
+            element_info: element_info(2, &[("type", "restriction"), ("restriction", "no_left_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 30, "from"),
+                member(RelationMemberType::Node, 5, "via"),
+                member(RelationMemberType::Way, 40, "to"),
+            ],
+        };
+
+        let index = RestrictionIndex::build(&[malformed, valid]);
+
+        assert!(!index.is_turn_allowed(30, 5, 40));
+        match index.skipped() {
+            [RestrictionError::MissingVia(1)] => {}
+            other => panic!("expected a single MissingVia(1), got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_via_way_restriction_is_enforced_through_is_turn_allowed() {
+        let relations = vec![Relation {
+            element_info: element_info(1, &[("type", "restriction"), ("restriction", "no_u_turn")]),
+            members: vec![
+                member(RelationMemberType::Way, 10, "from"),
+                member(RelationMemberType::Way, 15, "via"),
+                member(RelationMemberType::Way, 20, "to"),
+            ],
+        }];
+
+        let index = RestrictionIndex::build(&relations);
+
+        assert!(!index.is_turn_allowed(10, 15, 20));
+        assert!(index.is_turn_allowed(10, 15, 30));
+    }
+}
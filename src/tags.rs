@@ -0,0 +1,175 @@
+use std::iter::FromIterator;
+use std::slice::Iter;
+
+/// An ordered collection of OSM key/value tags.
+///
+/// OSM tags are free-form strings, but several conventions are used across
+/// the ecosystem to pack structured information into them: qualified keys
+/// such as `lanes:bus:forward` carry a `:`-delimited namespace/suffix, and
+/// values such as `cuisine=pizza;kebab` pack a categorical list separated by
+/// `;`. `Tags` keeps insertion order (mirroring the order tags appear in the
+/// source data) and exposes both raw lookups and these OSM-aware helpers.
+///
+/// See: [OSM wiki - Tags](https://wiki.openstreetmap.org/wiki/Tags)
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Tags {
+    entries: Vec<(String, String)>,
+}
+
+impl Tags {
+    /// Create an empty `Tags` collection.
+    pub fn new() -> Tags {
+        Tags { entries: Vec::new() }
+    }
+
+    /// Insert a key/value pair, overwriting any existing value for `key`
+    /// while keeping its original position.
+    pub fn insert<K: Into<String>, V: Into<String>>(&mut self, key: K, value: V) {
+        let key = key.into();
+        match self.entries.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value.into(),
+            None => self.entries.push((key, value.into())),
+        }
+    }
+
+    /// Look up the raw value of `key`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Whether `key` is present, regardless of its value.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// The number of tags.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether there are no tags at all.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Iterate over `(key, value)` pairs in insertion order.
+    pub fn iter(&self) -> TagsIter<'_> {
+        TagsIter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Split a qualified key such as `lanes:bus:forward` into its
+    /// `:`-delimited segments (e.g. prefix/infix/suffix).
+    pub fn parts(key: &str) -> Vec<&str> {
+        key.split(':').collect()
+    }
+
+    /// The leading segment of a qualified key, e.g. `lanes` for
+    /// `lanes:bus:forward`. Returns the whole key when it has no `:`.
+    pub fn namespace(key: &str) -> &str {
+        key.split(':').next().unwrap_or(key)
+    }
+
+    /// Split a value on `;` into the categorical list OSM uses, e.g.
+    /// `cuisine=pizza;kebab` yields `["pizza", "kebab"]`. Segments are
+    /// trimmed of surrounding whitespace.
+    pub fn values(value: &str) -> Vec<&str> {
+        value.split(';').map(str::trim).collect()
+    }
+
+    /// Interpret the value of `key` as an OSM boolean: `yes`/`true`/`1` are
+    /// `true`, `no`/`false`/`0` are `false`, anything else is `None`.
+    pub fn get_bool(&self, key: &str) -> Option<bool> {
+        match self.get(key)? {
+            "yes" | "true" | "1" => Some(true),
+            "no" | "false" | "0" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// Parse the value of `key` as an `i64`.
+    pub fn get_i64(&self, key: &str) -> Option<i64> {
+        self.get(key)?.parse().ok()
+    }
+
+    /// Parse the value of `key` as an `f64`.
+    pub fn get_f64(&self, key: &str) -> Option<f64> {
+        self.get(key)?.parse().ok()
+    }
+}
+
+impl<K: Into<String>, V: Into<String>> FromIterator<(K, V)> for Tags {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> Tags {
+        let mut tags = Tags::new();
+        for (k, v) in iter {
+            tags.insert(k, v);
+        }
+        tags
+    }
+}
+
+/// Iterator over the `(key, value)` pairs of a [`Tags`] collection, in
+/// insertion order.
+pub struct TagsIter<'a> {
+    inner: Iter<'a, (String, String)>,
+}
+
+impl<'a> Iterator for TagsIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_insert_overwrites_in_place() {
+        let mut tags = Tags::new();
+        tags.insert("highway", "residential");
+        tags.insert("name", "Main Street");
+        tags.insert("highway", "primary");
+
+        assert_eq!(tags.get("highway"), Some("primary"));
+        assert_eq!(
+            tags.iter().collect::<Vec<_>>(),
+            vec![("highway", "primary"), ("name", "Main Street")]
+        );
+    }
+
+    #[test]
+    fn test_namespace_and_parts() {
+        assert_eq!(Tags::namespace("lanes:bus:forward"), "lanes");
+        assert_eq!(Tags::namespace("name"), "name");
+        assert_eq!(
+            Tags::parts("lanes:bus:forward"),
+            vec!["lanes", "bus", "forward"]
+        );
+    }
+
+    #[test]
+    fn test_values_splits_on_semicolon() {
+        assert_eq!(Tags::values("pizza;kebab"), vec!["pizza", "kebab"]);
+        assert_eq!(Tags::values("pizza"), vec!["pizza"]);
+    }
+
+    #[test]
+    fn test_typed_getters() {
+        let mut tags = Tags::new();
+        tags.insert("oneway", "yes");
+        tags.insert("lanes", "3");
+        tags.insert("maxspeed", "13.5");
+
+        assert_eq!(tags.get_bool("oneway"), Some(true));
+        assert_eq!(tags.get_bool("missing"), None);
+        assert_eq!(tags.get_i64("lanes"), Some(3));
+        assert_eq!(tags.get_f64("maxspeed"), Some(13.5));
+    }
+}
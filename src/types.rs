@@ -4,6 +4,7 @@ extern crate geo_types;
 use self::chrono::{DateTime, Utc};
 use self::geo_types::Point;
 use std::convert::From;
+use tags::Tags;
 
 /// A structure containing all the common attributes
 /// of the basic OSM data types
@@ -18,7 +19,7 @@ pub struct ElementInfo {
     pub visible: Option<bool>,
     pub version: Option<i32>,
     pub changeset: Option<i64>,
-    pub tags: Vec<String>,
+    pub tags: Tags,
 }
 
 impl ElementInfo {
@@ -31,7 +32,7 @@ impl ElementInfo {
             visible: Option::None,
             version: Option::None,
             changeset: Option::None,
-            tags: vec![],
+            tags: Tags::new(),
         }
     }
 }
@@ -85,11 +86,13 @@ impl PartialEq for Node {
 /// barrier=* they are not considered as an area.
 ///
 /// See: [OSM wiki - Way](https://wiki.openstreetmap.org/wiki/Way)
+#[derive(Debug)]
 pub struct Way {
     pub element_info: ElementInfo,
     pub nodes: Vec<i64>,
 }
 
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
 pub enum RelationMemberType {
     Node,
     Way,
@@ -99,6 +102,7 @@ pub enum RelationMemberType {
 /// Each Relation consists of multiple members. Each member holds which `member_type` (Node,
 /// Way, Relation) it is, the `reference` (id) of it and what `role` it has inside the
 /// relation.
+#[derive(Debug)]
 pub struct RelationMember {
     pub member_type: RelationMemberType,
     pub reference: i64,
@@ -109,6 +113,7 @@ pub struct RelationMember {
 /// multiple other Elements, such as nodes, ways or even other relations.
 ///
 /// See: [OSM wiki - Relation](https://wiki.openstreetmap.org/wiki/Relation)
+#[derive(Debug)]
 pub struct Relation {
     pub element_info: ElementInfo,
     pub members: Vec<RelationMember>,